@@ -0,0 +1,152 @@
+//! [`CachingDaemonClient`], a thin wrapper around [`DaemonClient`] that
+//! memoizes responses which can never change once a block is on the chain:
+//! block hashes, block headers by height, and blocks at a height below the
+//! current tip. Everything else (`get_block_count`, `submit_block`, ...) is
+//! forwarded straight through via `Deref` and never cached.
+
+use failure::Fallible;
+use std::collections::{HashMap, VecDeque};
+use std::ops::Deref;
+use std::sync::Mutex;
+
+use crate::daemon::{BlockHeader, DaemonClient, GetBlockResult, HeightOrHash};
+use crate::util::BlockHash;
+
+const DEFAULT_CAPACITY: usize = 10_000;
+
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+enum CacheKey {
+    BlockHash(u64),
+    BlockHeader(u64),
+    Block(u64),
+}
+
+struct Cache {
+    capacity: usize,
+    order: VecDeque<CacheKey>,
+    block_hashes: HashMap<u64, BlockHash>,
+    block_headers: HashMap<u64, BlockHeader>,
+    blocks: HashMap<u64, GetBlockResult>,
+}
+
+impl Cache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            order: VecDeque::new(),
+            block_hashes: HashMap::new(),
+            block_headers: HashMap::new(),
+            blocks: HashMap::new(),
+        }
+    }
+
+    /// Records that `key` was just inserted, evicting the oldest entry if
+    /// the cache has grown past its capacity.
+    fn remember(&mut self, key: CacheKey) {
+        self.order.push_back(key);
+        if self.order.len() > self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                match evicted {
+                    CacheKey::BlockHash(height) => {
+                        self.block_hashes.remove(&height);
+                    }
+                    CacheKey::BlockHeader(height) => {
+                        self.block_headers.remove(&height);
+                    }
+                    CacheKey::Block(height) => {
+                        self.blocks.remove(&height);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct CachingDaemonClient {
+    inner: DaemonClient,
+    cache: Mutex<Cache>,
+}
+
+impl Deref for CachingDaemonClient {
+    type Target = DaemonClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl CachingDaemonClient {
+    pub fn new(inner: DaemonClient) -> Self {
+        Self::with_capacity(inner, DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(inner: DaemonClient, capacity: usize) -> Self {
+        Self {
+            inner,
+            cache: Mutex::new(Cache::new(capacity)),
+        }
+    }
+
+    pub fn into_inner(self) -> DaemonClient {
+        self.inner
+    }
+
+    /// Only cached once `height` is below the current tip — see
+    /// [`CachingDaemonClient::get_block`].
+    pub async fn on_get_block_hash(&self, height: u64) -> Fallible<BlockHash> {
+        if let Some(hash) = self.cache.lock().unwrap().block_hashes.get(&height) {
+            return Ok(*hash);
+        }
+
+        let tip = await!(self.inner.get_block_count())?;
+        let hash = await!(self.inner.on_get_block_hash(height))?;
+
+        if u128::from(height) + 1 < tip {
+            let mut cache = self.cache.lock().unwrap();
+            cache.block_hashes.insert(height, hash);
+            cache.remember(CacheKey::BlockHash(height));
+        }
+
+        Ok(hash)
+    }
+
+    /// Only cached once `height` is below the current tip — see
+    /// [`CachingDaemonClient::get_block`].
+    pub async fn get_block_header_by_height(&self, height: u64) -> Fallible<BlockHeader> {
+        if let Some(header) = self.cache.lock().unwrap().block_headers.get(&height) {
+            return Ok(header.clone());
+        }
+
+        let tip = await!(self.inner.get_block_count())?;
+        let header = await!(self.inner.get_block_header_by_height(height))?;
+
+        if u128::from(height) + 1 < tip {
+            let mut cache = self.cache.lock().unwrap();
+            cache.block_headers.insert(height, header.clone());
+            cache.remember(CacheKey::BlockHeader(height));
+        }
+
+        Ok(header)
+    }
+
+    /// Like [`DaemonClient::get_block`] restricted to lookups by height, but
+    /// only cached once `height` is below the current tip — the tip itself
+    /// (and anything above it) can still be reorganized, so it is always
+    /// fetched fresh.
+    pub async fn get_block(&self, height: u64) -> Fallible<GetBlockResult> {
+        if let Some(block) = self.cache.lock().unwrap().blocks.get(&height) {
+            return Ok(block.clone());
+        }
+
+        let tip = await!(self.inner.get_block_count())?;
+        let block = await!(self.inner.get_block(HeightOrHash::Height(height)))?;
+
+        if u128::from(height) + 1 < tip {
+            let mut cache = self.cache.lock().unwrap();
+            cache.blocks.insert(height, block.clone());
+            cache.remember(CacheKey::Block(height));
+        }
+
+        Ok(block)
+    }
+}
@@ -0,0 +1,284 @@
+//! `DaemonClient` and its regtest-only extension.
+
+use failure::{bail, format_err, Fallible};
+use jsonrpc_core::types::*;
+use monero::Address;
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+use crate::epee;
+use crate::rpc::{rpc_method, RpcClient};
+use crate::util::{HashString, HashType};
+use crate::{BlockHash, BlockHashingBlob};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockCount {
+    pub count: u128,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockTemplate {
+    pub blockhashing_blob: HashString<BlockHashingBlob>,
+    pub blocktemplate_blob: String,
+    pub difficulty: u64,
+    pub expected_reward: u64,
+    pub height: u64,
+    pub prev_hash: HashString<BlockHash>,
+    pub reserved_offset: u64,
+    pub untrusted: bool,
+}
+
+/// Header fields of a single block, as returned by
+/// [`DaemonClient::get_block_header_by_height`] and friends.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub difficulty: u64,
+    pub hash: HashString<BlockHash>,
+    pub height: u64,
+    pub nonce: u32,
+    pub num_txes: u64,
+    pub orphan_status: bool,
+    pub prev_hash: HashString<BlockHash>,
+    pub reward: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct BlockHeaderResponse {
+    block_header: BlockHeader,
+    untrusted: bool,
+}
+
+/// Response to [`DaemonClient::get_block`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetBlockResult {
+    pub blob: String,
+    pub block_header: BlockHeader,
+    pub json: String,
+    pub untrusted: bool,
+}
+
+/// Selects a block by height or by hash for [`DaemonClient::get_block`].
+#[derive(Copy, Clone, Debug)]
+pub enum HeightOrHash {
+    Height(u64),
+    Hash(BlockHash),
+}
+
+/// A single output, as returned by [`DaemonClient::get_outs`].
+#[derive(Clone, Debug)]
+pub struct OutKey {
+    pub height: u64,
+    pub key: [u8; 32],
+    pub mask: [u8; 32],
+    pub txid: BlockHash,
+    pub unlocked: bool,
+}
+
+#[derive(Debug)]
+pub struct DaemonClient {
+    pub(crate) inner: RpcClient,
+}
+
+#[derive(Debug)]
+pub struct RegtestDaemonClient(pub DaemonClient);
+
+impl Deref for RegtestDaemonClient {
+    type Target = DaemonClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DaemonClient {
+    pub async fn get_block_count(&self) -> Fallible<u128> {
+        Ok(await!(self
+            .inner
+            .request::<crate::MoneroResult<BlockCount>>("get_block_count", Params::Array(vec![])))?
+        .into_inner()
+        .count)
+    }
+
+    pub async fn on_get_block_hash(&self, height: u64) -> Fallible<BlockHash> {
+        await!(self.inner.request::<HashString<BlockHash>>(
+            "on_get_block_hash",
+            Params::Array(vec![height.into()])
+        ))
+        .map(|v| v.0)
+    }
+
+    rpc_method! {
+        pub async fn get_block_template(&self, wallet_address: Address, reserve_size: u64) -> Fallible<BlockTemplate> via "get_block_template", status;
+    }
+
+    pub async fn submit_block(&self, block_blob_data: String) -> Fallible<String> {
+        await!(self
+            .inner
+            .request("submit_block", Params::Array(vec![block_blob_data.into()])))
+    }
+
+    pub async fn get_block_header_by_height(&self, height: u64) -> Fallible<BlockHeader> {
+        let mut params = serde_json::Map::new();
+        params.insert("height".to_string(), height.into());
+
+        Ok(await!(self
+            .inner
+            .request::<crate::MoneroResult<BlockHeaderResponse>>(
+                "get_block_header_by_height",
+                Params::Map(params)
+            ))?
+        .into_inner()
+        .block_header)
+    }
+
+    pub async fn get_block_header_by_hash(&self, hash: BlockHash) -> Fallible<BlockHeader> {
+        let mut params = serde_json::Map::new();
+        params.insert(
+            "hash".to_string(),
+            serde_json::to_value(HashString(hash)).unwrap(),
+        );
+
+        Ok(await!(self
+            .inner
+            .request::<crate::MoneroResult<BlockHeaderResponse>>(
+                "get_block_header_by_hash",
+                Params::Map(params)
+            ))?
+        .into_inner()
+        .block_header)
+    }
+
+    pub async fn get_last_block_header(&self) -> Fallible<BlockHeader> {
+        Ok(await!(self
+            .inner
+            .request::<crate::MoneroResult<BlockHeaderResponse>>(
+                "get_last_block_header",
+                Params::Map(serde_json::Map::new())
+            ))?
+        .into_inner()
+        .block_header)
+    }
+
+    pub async fn get_block(&self, height_or_hash: HeightOrHash) -> Fallible<GetBlockResult> {
+        let mut params = serde_json::Map::new();
+        match height_or_hash {
+            HeightOrHash::Height(height) => {
+                params.insert("height".to_string(), height.into());
+            }
+            HeightOrHash::Hash(hash) => {
+                params.insert(
+                    "hash".to_string(),
+                    serde_json::to_value(HashString(hash)).unwrap(),
+                );
+            }
+        }
+
+        Ok(await!(self
+            .inner
+            .request::<crate::MoneroResult<GetBlockResult>>("get_block", Params::Map(params)))?
+        .into_inner())
+    }
+
+    /// Looks up a set of outputs by their (amount, global index) pair over
+    /// the binary `/get_outs.bin` endpoint.
+    pub async fn get_outs(&self, outputs: &[(u64, u64)]) -> Fallible<Vec<OutKey>> {
+        let entries = outputs
+            .iter()
+            .map(|&(amount, index)| {
+                epee::Value::Section(vec![
+                    ("amount".to_string(), epee::Value::U64(amount)),
+                    ("index".to_string(), epee::Value::U64(index)),
+                ])
+            })
+            .collect();
+
+        let request = vec![
+            (
+                "outputs".to_string(),
+                epee::Value::Array(epee::TAG_OBJECT, entries),
+            ),
+            ("get_txid".to_string(), epee::Value::Bool(true)),
+        ];
+
+        let response = await!(self.inner.request_bin("get_outs.bin", request))?;
+
+        epee::field(&response, "outs")
+            .and_then(epee::Value::as_array)
+            .ok_or_else(|| format_err!("missing \"outs\" field in get_outs response"))?
+            .iter()
+            .map(|entry| {
+                let fields = entry
+                    .as_section()
+                    .ok_or_else(|| format_err!("malformed entry in get_outs response"))?;
+                out_key_from_section(fields)
+            })
+            .collect()
+    }
+
+    /// Looks up the global output indices of a transaction's outputs over
+    /// the binary `/get_o_indexes.bin` endpoint.
+    pub async fn get_o_indexes(&self, txid: BlockHash) -> Fallible<Vec<u64>> {
+        let request = vec![(
+            "txid".to_string(),
+            epee::Value::Str(txid.bytes().to_vec()),
+        )];
+
+        let response = await!(self.inner.request_bin("get_o_indexes.bin", request))?;
+
+        epee::field(&response, "o_indexes")
+            .and_then(epee::Value::as_array)
+            .ok_or_else(|| format_err!("missing \"o_indexes\" field in get_o_indexes response"))?
+            .iter()
+            .map(|v| v.as_u64().ok_or_else(|| format_err!("malformed o_indexes entry")))
+            .collect()
+    }
+
+    /// Enable additional functions for regtest mode
+    pub fn regtest(self) -> RegtestDaemonClient {
+        RegtestDaemonClient(self)
+    }
+}
+
+fn out_key_from_section(fields: &epee::Section) -> Fallible<OutKey> {
+    let height = epee::field(fields, "height")
+        .and_then(epee::Value::as_u64)
+        .ok_or_else(|| format_err!("missing \"height\" field in get_outs entry"))?;
+    let key = fixed_32_bytes(fields, "key")?;
+    let mask = fixed_32_bytes(fields, "mask")?;
+    let txid = BlockHash::from_slice(&fixed_32_bytes(fields, "txid")?);
+    let unlocked = epee::field(fields, "unlocked")
+        .and_then(epee::Value::as_bool)
+        .ok_or_else(|| format_err!("missing \"unlocked\" field in get_outs entry"))?;
+
+    Ok(OutKey {
+        height,
+        key,
+        mask,
+        txid,
+        unlocked,
+    })
+}
+
+fn fixed_32_bytes(fields: &epee::Section, key: &str) -> Fallible<[u8; 32]> {
+    let bytes = epee::field(fields, key)
+        .and_then(epee::Value::as_bytes)
+        .ok_or_else(|| format_err!("missing \"{}\" field in get_outs entry", key))?;
+    let mut out = [0u8; 32];
+    if bytes.len() != out.len() {
+        bail!("\"{}\" field has unexpected length {}", key, bytes.len());
+    }
+    out.copy_from_slice(bytes);
+    Ok(out)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GenerateBlocksResponse {
+    pub height: u128,
+}
+
+impl RegtestDaemonClient {
+    rpc_method! {
+        pub async fn generate_blocks(&self, amount_of_blocks: u128, wallet_address: Address) -> Fallible<GenerateBlocksResponse> via "generateblocks", status;
+    }
+}
@@ -0,0 +1,324 @@
+//! Minimal codec for Monero's epee "portable storage" binary format.
+//!
+//! A handful of monerod endpoints (`/get_outs.bin`, `/get_o_indexes.bin`, ...)
+//! don't speak JSON-RPC at all; they take and return this binary format
+//! instead. This module only implements the subset needed to build requests
+//! and read responses for those endpoints — it is not a general-purpose
+//! serde backend.
+//!
+//! Layout: a 9-byte header (an 8-byte signature followed by a 1-byte format
+//! version), then a root section. A section is a varint entry count followed
+//! by that many entries; each entry is `[1-byte key length][key bytes][1-byte
+//! type tag][value]`. Varints are little-endian with the low 2 bits of the
+//! first byte encoding the width (`00` → 1 byte, `01` → 2, `10` → 4, `11` →
+//! 8) and the remaining bits holding the value shifted left by 2. An array of
+//! a base type is tagged with the base type's tag OR'd with
+//! [`TAG_FLAG_ARRAY`], and its value is a varint element count followed by
+//! that many bare (untagged) values of the base type.
+
+use failure::{bail, Fallible};
+
+const SIGNATURE: [u8; 8] = [0x01, 0x11, 0x01, 0x01, 0x01, 0x01, 0x02, 0x01];
+const FORMAT_VERSION: u8 = 0x01;
+
+pub const TAG_INT64: u8 = 1;
+pub const TAG_INT32: u8 = 2;
+pub const TAG_INT16: u8 = 3;
+pub const TAG_INT8: u8 = 4;
+pub const TAG_UINT64: u8 = 5;
+pub const TAG_UINT32: u8 = 6;
+pub const TAG_UINT16: u8 = 7;
+pub const TAG_UINT8: u8 = 8;
+pub const TAG_BOOL: u8 = 11;
+pub const TAG_STRING: u8 = 10;
+pub const TAG_OBJECT: u8 = 12;
+pub const TAG_FLAG_ARRAY: u8 = 0x80;
+
+pub type Section = Vec<(String, Value)>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    U64(u64),
+    U32(u32),
+    U16(u16),
+    U8(u8),
+    I64(i64),
+    I32(i32),
+    I16(i16),
+    I8(i8),
+    Bool(bool),
+    Str(Vec<u8>),
+    Section(Section),
+    /// An array of values of a single base type tag (e.g. `TAG_OBJECT` for
+    /// an array of sections).
+    Array(u8, Vec<Value>),
+}
+
+impl Value {
+    fn tag(&self) -> u8 {
+        match self {
+            Value::U64(_) => TAG_UINT64,
+            Value::U32(_) => TAG_UINT32,
+            Value::U16(_) => TAG_UINT16,
+            Value::U8(_) => TAG_UINT8,
+            Value::I64(_) => TAG_INT64,
+            Value::I32(_) => TAG_INT32,
+            Value::I16(_) => TAG_INT16,
+            Value::I8(_) => TAG_INT8,
+            Value::Bool(_) => TAG_BOOL,
+            Value::Str(_) => TAG_STRING,
+            Value::Section(_) => TAG_OBJECT,
+            Value::Array(base, _) => base | TAG_FLAG_ARRAY,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::U64(v) => Some(*v),
+            Value::U32(v) => Some(u64::from(*v)),
+            Value::U16(v) => Some(u64::from(*v)),
+            Value::U8(v) => Some(u64::from(*v)),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Str(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_section(&self) -> Option<&Section> {
+        match self {
+            Value::Section(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(_, v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Looks up an entry by key in a decoded (or to-be-encoded) section.
+pub fn field<'a>(section: &'a [(String, Value)], key: &str) -> Option<&'a Value> {
+    section.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+pub fn encode(section: &Section) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&SIGNATURE);
+    buf.push(FORMAT_VERSION);
+    write_section(&mut buf, section);
+    buf
+}
+
+pub fn decode(input: &[u8]) -> Fallible<Section> {
+    if input.len() < 9 || input[..8] != SIGNATURE || input[8] != FORMAT_VERSION {
+        bail!("invalid epee portable-storage header");
+    }
+    let mut rest = &input[9..];
+    read_section(&mut rest)
+}
+
+fn write_varint(buf: &mut Vec<u8>, value: u64) {
+    if value <= 0x3f {
+        buf.push((value as u8) << 2);
+    } else if value <= 0x3fff {
+        buf.extend_from_slice(&(((value as u16) << 2) | 0b01).to_le_bytes());
+    } else if value <= 0x3fff_ffff {
+        buf.extend_from_slice(&(((value as u32) << 2) | 0b10).to_le_bytes());
+    } else {
+        buf.extend_from_slice(&((value << 2) | 0b11).to_le_bytes());
+    }
+}
+
+fn read_varint(buf: &mut &[u8]) -> Fallible<u64> {
+    if buf.is_empty() {
+        bail!("unexpected end of input while reading a varint");
+    }
+    let (value, len) = match buf[0] & 0b11 {
+        0b00 => (u64::from(buf[0] >> 2), 1),
+        0b01 => {
+            take(buf, 2)?;
+            (u64::from(u16::from_le_bytes([buf[0], buf[1]]) >> 2), 2)
+        }
+        0b10 => {
+            take(buf, 4)?;
+            (
+                u64::from(u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) >> 2),
+                4,
+            )
+        }
+        _ => {
+            take(buf, 8)?;
+            (
+                u64::from_le_bytes([
+                    buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7],
+                ]) >> 2,
+                8,
+            )
+        }
+    };
+    *buf = &buf[len..];
+    Ok(value)
+}
+
+/// Checks that at least `len` bytes remain, without consuming them.
+fn take(buf: &[u8], len: usize) -> Fallible<()> {
+    if buf.len() < len {
+        bail!("unexpected end of input");
+    }
+    Ok(())
+}
+
+fn write_section(buf: &mut Vec<u8>, section: &Section) {
+    write_varint(buf, section.len() as u64);
+    for (key, value) in section {
+        buf.push(key.len() as u8);
+        buf.extend_from_slice(key.as_bytes());
+        buf.push(value.tag());
+        write_value(buf, value);
+    }
+}
+
+fn write_value(buf: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::U64(v) => buf.extend_from_slice(&v.to_le_bytes()),
+        Value::U32(v) => buf.extend_from_slice(&v.to_le_bytes()),
+        Value::U16(v) => buf.extend_from_slice(&v.to_le_bytes()),
+        Value::U8(v) => buf.push(*v),
+        Value::I64(v) => buf.extend_from_slice(&v.to_le_bytes()),
+        Value::I32(v) => buf.extend_from_slice(&v.to_le_bytes()),
+        Value::I16(v) => buf.extend_from_slice(&v.to_le_bytes()),
+        Value::I8(v) => buf.push(*v as u8),
+        Value::Bool(v) => buf.push(*v as u8),
+        Value::Str(v) => {
+            write_varint(buf, v.len() as u64);
+            buf.extend_from_slice(v);
+        }
+        Value::Section(v) => write_section(buf, v),
+        Value::Array(_, items) => {
+            write_varint(buf, items.len() as u64);
+            for item in items {
+                write_value(buf, item);
+            }
+        }
+    }
+}
+
+fn read_section(buf: &mut &[u8]) -> Fallible<Section> {
+    let count = read_varint(buf)?;
+    let mut section = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        take(buf, 1)?;
+        let key_len = buf[0] as usize;
+        *buf = &buf[1..];
+        take(buf, key_len)?;
+        let key = String::from_utf8(buf[..key_len].to_vec())?;
+        *buf = &buf[key_len..];
+        take(buf, 1)?;
+        let tag = buf[0];
+        *buf = &buf[1..];
+        let value = read_value(buf, tag)?;
+        section.push((key, value));
+    }
+    Ok(section)
+}
+
+fn read_value(buf: &mut &[u8], tag: u8) -> Fallible<Value> {
+    if tag & TAG_FLAG_ARRAY != 0 {
+        let base = tag & !TAG_FLAG_ARRAY;
+        let count = read_varint(buf)?;
+        let mut items = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            items.push(read_scalar(buf, base)?);
+        }
+        Ok(Value::Array(base, items))
+    } else {
+        read_scalar(buf, tag)
+    }
+}
+
+fn read_scalar(buf: &mut &[u8], tag: u8) -> Fallible<Value> {
+    Ok(match tag {
+        TAG_UINT64 => {
+            take(buf, 8)?;
+            let v = u64::from_le_bytes([
+                buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7],
+            ]);
+            *buf = &buf[8..];
+            Value::U64(v)
+        }
+        TAG_UINT32 => {
+            take(buf, 4)?;
+            let v = u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]);
+            *buf = &buf[4..];
+            Value::U32(v)
+        }
+        TAG_UINT16 => {
+            take(buf, 2)?;
+            let v = u16::from_le_bytes([buf[0], buf[1]]);
+            *buf = &buf[2..];
+            Value::U16(v)
+        }
+        TAG_UINT8 => {
+            take(buf, 1)?;
+            let v = buf[0];
+            *buf = &buf[1..];
+            Value::U8(v)
+        }
+        TAG_INT64 => {
+            take(buf, 8)?;
+            let v = i64::from_le_bytes([
+                buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7],
+            ]);
+            *buf = &buf[8..];
+            Value::I64(v)
+        }
+        TAG_INT32 => {
+            take(buf, 4)?;
+            let v = i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]);
+            *buf = &buf[4..];
+            Value::I32(v)
+        }
+        TAG_INT16 => {
+            take(buf, 2)?;
+            let v = i16::from_le_bytes([buf[0], buf[1]]);
+            *buf = &buf[2..];
+            Value::I16(v)
+        }
+        TAG_INT8 => {
+            take(buf, 1)?;
+            let v = buf[0] as i8;
+            *buf = &buf[1..];
+            Value::I8(v)
+        }
+        TAG_BOOL => {
+            take(buf, 1)?;
+            let v = buf[0] != 0;
+            *buf = &buf[1..];
+            Value::Bool(v)
+        }
+        TAG_STRING => {
+            let len = read_varint(buf)? as usize;
+            take(buf, len)?;
+            let v = buf[..len].to_vec();
+            *buf = &buf[len..];
+            Value::Str(v)
+        }
+        TAG_OBJECT => Value::Section(read_section(buf)?),
+        other => bail!("unsupported epee type tag: {}", other),
+    })
+}
@@ -0,0 +1,164 @@
+//! The JSON-RPC transport shared by [`DaemonClient`](crate::DaemonClient) and
+//! [`WalletClient`](crate::WalletClient), plus the [`rpc_method!`] macro used
+//! throughout this crate to declare individual RPC calls.
+
+use failure::{format_err, Fallible};
+use futures::compat::*;
+use jsonrpc_core::types::*;
+use log::trace;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::epee;
+use crate::{DaemonClient, WalletClient};
+
+/// Matches the `{}` (plus whatever other fields, e.g. `status`) that
+/// status-only RPCs reply with on success. `()` only deserializes from a
+/// bare `null`, so methods with nothing meaningful to return should
+/// deserialize into this and map it to `()`, rather than declaring `Fallible<()>`
+/// directly.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Empty {}
+
+/// Serializes `value` and inserts it into `params` under `key`, unless it
+/// serializes to `null` (e.g. an `Option` argument that was `None`) — in
+/// which case the key is left out entirely, matching how an absent optional
+/// is meant to look to monerod.
+pub(crate) fn insert_param<T: serde::Serialize>(
+    params: &mut serde_json::Map<String, Value>,
+    key: &str,
+    value: T,
+) {
+    let value = serde_json::to_value(value).unwrap();
+    if !value.is_null() {
+        params.insert(key.to_string(), value);
+    }
+}
+
+/// Declares a set of async RPC methods on the surrounding `impl` block.
+///
+/// Each declaration lists its named arguments and return type, and the
+/// `json_rpc` method name it maps to. The arguments are serialized under
+/// their Rust parameter name into a `Params::Map` (an absent `Option`
+/// argument is simply omitted rather than sent as `null`), the call is
+/// dispatched through [`RpcClient::request`], and the `result` field of the
+/// response is deserialized into the declared return type. Appending `,
+/// status` first unwraps a [`MoneroResult`](crate::MoneroResult) envelope,
+/// for RPCs that reply with a top-level `"status"` field next to the
+/// payload.
+macro_rules! rpc_method {
+    ($(
+        $(#[$meta:meta])*
+        $vis:vis async fn $name:ident(&self $(, $arg:ident : $arg_ty:ty)* $(,)?) -> Fallible<$ret:ty> via $method:expr $(, $status:ident)?;
+    )*) => {
+        $(
+            rpc_method!(@method $(#[$meta])* $vis async fn $name(&self $(, $arg: $arg_ty)*) -> Fallible<$ret> via $method $(, $status)?;);
+        )*
+    };
+
+    (@method $(#[$meta:meta])* $vis:vis async fn $name:ident(&self $(, $arg:ident : $arg_ty:ty)*) -> Fallible<$ret:ty> via $method:expr;) => {
+        $(#[$meta])*
+        $vis async fn $name(&self, $($arg: $arg_ty),*) -> Fallible<$ret> {
+            #[allow(unused_mut)]
+            let mut params = serde_json::Map::new();
+            $(
+                crate::rpc::insert_param(&mut params, stringify!($arg), $arg);
+            )*
+            await!(self.inner.request($method, Params::Map(params)))
+        }
+    };
+
+    (@method $(#[$meta:meta])* $vis:vis async fn $name:ident(&self $(, $arg:ident : $arg_ty:ty)*) -> Fallible<$ret:ty> via $method:expr, status;) => {
+        $(#[$meta])*
+        $vis async fn $name(&self, $($arg: $arg_ty),*) -> Fallible<$ret> {
+            #[allow(unused_mut)]
+            let mut params = serde_json::Map::new();
+            $(
+                crate::rpc::insert_param(&mut params, stringify!($arg), $arg);
+            )*
+            Ok(await!(self
+                .inner
+                .request::<crate::MoneroResult<$ret>>($method, Params::Map(params)))?
+            .into_inner())
+        }
+    };
+}
+
+pub(crate) use rpc_method;
+
+#[derive(Debug)]
+pub struct RpcClient {
+    client: reqwest::r#async::Client,
+    addr: String,
+}
+
+impl RpcClient {
+    pub fn new(addr: String) -> Self {
+        Self {
+            client: reqwest::r#async::Client::new(),
+            addr,
+        }
+    }
+
+    pub(crate) async fn request<T>(&self, method: &'static str, params: Params) -> Fallible<T>
+    where
+        for<'de> T: Deserialize<'de>,
+    {
+        let addr = format!("{}/json_rpc", &self.addr);
+
+        let body = serde_json::to_string(&MethodCall {
+            jsonrpc: Some(Version::V2),
+            method: method.to_string(),
+            params,
+            id: Id::Str(Uuid::new_v4().to_string()),
+        })
+        .unwrap();
+
+        trace!("Sending {} to {}", body, &addr);
+
+        let rsp = await!(await!(self
+            .client
+            .post(&addr)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .compat())?
+        .json::<response::Output>()
+        .compat())?;
+
+        let v = jsonrpc_core::Result::<Value>::from(rsp)
+            .map_err(|e| format_err!("Code: {:?}, Message: {}", e.code, e.message))?;
+
+        Ok(serde_json::from_value(v)?)
+    }
+
+    /// Like [`RpcClient::request`], but for the handful of monerod endpoints
+    /// that speak the epee "portable storage" binary format instead of
+    /// JSON-RPC (`/get_outs.bin`, `/get_o_indexes.bin`, ...).
+    pub(crate) async fn request_bin(&self, path: &'static str, body: epee::Section) -> Fallible<epee::Section> {
+        let addr = format!("{}/{}", &self.addr, path);
+        let payload = epee::encode(&body);
+
+        trace!("Sending {} bytes to {}", payload.len(), &addr);
+
+        let bytes = await!(await!(self
+            .client
+            .post(&addr)
+            .header("Content-Type", "application/octet-stream")
+            .body(payload)
+            .send()
+            .compat())?
+        .bytes()
+        .compat())?;
+
+        epee::decode(&bytes)
+    }
+
+    pub fn daemon(self) -> DaemonClient {
+        DaemonClient { inner: self }
+    }
+
+    pub fn wallet(self) -> WalletClient {
+        WalletClient { inner: self }
+    }
+}
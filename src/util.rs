@@ -0,0 +1,83 @@
+//! Shared serde helpers and wire types used across the daemon and wallet
+//! clients: fixed-size hashes, the `HashString` hex encoding, and the
+//! `MoneroResult` envelope that daemon RPCs wrap their payload in.
+
+use core::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub trait HashType: FromStr<Err = rustc_hex::FromHexError> {
+    fn bytes(&self) -> &[u8];
+}
+
+macro_rules! hash_type {
+    ($name:ident, $len:expr) => {
+        fixed_hash::construct_fixed_hash! {
+            pub struct $name($len);
+        }
+
+        impl HashType for $name {
+            fn bytes(&self) -> &[u8] {
+                self.as_bytes()
+            }
+        }
+    };
+}
+
+hash_type!(BlockHash, 32);
+hash_type!(BlockHashingBlob, 76);
+hash_type!(PrivateKey, 32);
+
+impl HashType for monero::PaymentId {
+    fn bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HashString<T>(pub T);
+
+impl<'a, T> Serialize for HashString<T>
+where
+    T: HashType,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&hex::encode(self.0.bytes()))
+    }
+}
+
+impl<'de, T> Deserialize<'de> for HashString<T>
+where
+    T: HashType,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = <&str>::deserialize(deserializer)?;
+        Ok(Self(T::from_str(s).map_err(serde::de::Error::custom)?))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Status {
+    OK,
+}
+
+/// Envelope used by daemon RPCs that reply with a top-level `"status"` field
+/// alongside the actual payload.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "status")]
+pub enum MoneroResult<T> {
+    OK(T),
+}
+
+impl<T> MoneroResult<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            MoneroResult::OK(v) => v,
+        }
+    }
+}
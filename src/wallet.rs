@@ -0,0 +1,403 @@
+//! `WalletClient` and its request/response types.
+
+use failure::Fallible;
+use jsonrpc_core::types::*;
+use monero::{Address, PaymentId};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::rpc::{rpc_method, RpcClient};
+use crate::util::{HashString, HashType, PrivateKey};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SubaddressBalanceData {
+    pub address: Address,
+    pub address_index: u64,
+    pub balance: u64,
+    pub label: String,
+    pub num_unspent_outputs: u64,
+    pub unlocked_balance: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BalanceData {
+    pub balance: u64,
+    pub multisig_import_needed: bool,
+    pub per_subaddress: Vec<SubaddressBalanceData>,
+    pub unlocked_balance: u64,
+}
+
+/// Response to [`WalletClient::create_account`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateAccountResult {
+    pub account_index: u64,
+    pub address: Address,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SubaddressAccountData {
+    pub account_index: u64,
+    pub balance: u64,
+    pub base_address: Address,
+    pub label: String,
+    pub tag: String,
+    pub unlocked_balance: u64,
+}
+
+/// Response to [`WalletClient::get_accounts`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetAccountsResult {
+    pub subaddress_accounts: Vec<SubaddressAccountData>,
+    pub total_balance: u64,
+    pub total_unlocked_balance: u64,
+}
+
+/// Response to [`WalletClient::create_address`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateAddressResult {
+    pub address: Address,
+    pub address_index: u64,
+}
+
+/// Response to [`WalletClient::check_tx_key`]: how much a transaction paid
+/// to a given address, verified from that transaction's secret key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CheckTxKeyResult {
+    pub confirmations: u64,
+    pub in_pool: bool,
+    pub received: u128,
+}
+
+/// A single transfer as returned by [`WalletClient::get_transfer_by_txid`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransferEntry {
+    pub address: Address,
+    pub amount: u128,
+    pub confirmations: u64,
+    pub double_spend_seen: bool,
+    pub fee: u128,
+    pub height: u64,
+    pub note: String,
+    pub payment_id: String,
+    pub subaddr_index: SubaddressIndex,
+    pub suggested_confirmations_threshold: u64,
+    pub timestamp: u64,
+    pub txid: String,
+    #[serde(rename = "type")]
+    pub transfer_type: String,
+    pub unlock_time: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SubaddressIndex {
+    pub major: u64,
+    pub minor: u64,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum TransferPriority {
+    Default,
+    Unimportant,
+    Elevated,
+    Priority,
+}
+
+impl Serialize for TransferPriority {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(match self {
+            TransferPriority::Default => 0,
+            TransferPriority::Unimportant => 1,
+            TransferPriority::Elevated => 2,
+            TransferPriority::Priority => 3,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for TransferPriority {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let v = u8::deserialize(deserializer)?;
+        Ok(match v {
+            0 => TransferPriority::Default,
+            1 => TransferPriority::Unimportant,
+            2 => TransferPriority::Elevated,
+            3 => TransferPriority::Priority,
+            other => {
+                return Err(serde::de::Error::custom(format!(
+                    "Invalid variant {}, expected 0-3",
+                    other
+                )))
+            }
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransferData {
+    pub amount: u128,
+    pub fee: u128,
+    pub multisig_txset: Vec<()>,
+    pub tx_blob: String,
+    pub tx_hash: String,
+    pub tx_key: String,
+    pub tx_metadata: String,
+    pub unsigned_txset: String,
+}
+
+/// Response to [`WalletClient::sweep_all`]. `sweep_all` may split the swept
+/// balance across several transactions, so each field is a parallel vector
+/// indexed by transaction.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SweepAllResult {
+    pub tx_hash_list: Vec<String>,
+    pub tx_key_list: Vec<String>,
+    pub amount_list: Vec<u128>,
+    pub fee_list: Vec<u128>,
+    pub weight_list: Vec<u64>,
+}
+
+/// Response to [`WalletClient::generate_from_keys`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GenerateFromKeysResult {
+    pub address: Address,
+    pub info: String,
+}
+
+#[derive(Debug)]
+pub struct WalletClient {
+    pub(crate) inner: RpcClient,
+}
+
+impl WalletClient {
+    rpc_method! {
+        pub async fn get_balance(&self, account: u64, addresses: Option<Vec<u64>>) -> Fallible<BalanceData> via "get_balance";
+        pub async fn get_address(&self, account: u64, addresses: Option<Vec<u64>>) -> Fallible<()> via "get_address";
+        pub async fn create_account(&self, label: Option<String>) -> Fallible<CreateAccountResult> via "create_account";
+        pub async fn get_accounts(&self, tag: Option<String>) -> Fallible<GetAccountsResult> via "get_accounts";
+        pub async fn create_address(&self, account_index: u64, label: Option<String>) -> Fallible<CreateAddressResult> via "create_address";
+        pub async fn check_tx_key(&self, txid: String, tx_key: String, address: Address) -> Fallible<CheckTxKeyResult> via "check_tx_key";
+    }
+
+    /// Creates a new wallet file. Returns nothing on success (`status` is the
+    /// only field in the reply), so this doesn't go through `rpc_method!`,
+    /// which would try to deserialize the empty-looking response as `()`.
+    pub async fn create_wallet(
+        &self,
+        filename: String,
+        password: Option<String>,
+        language: String,
+    ) -> Fallible<()> {
+        let mut params = serde_json::Map::new();
+        crate::rpc::insert_param(&mut params, "filename", filename);
+        crate::rpc::insert_param(&mut params, "password", password);
+        crate::rpc::insert_param(&mut params, "language", language);
+
+        await!(self
+            .inner
+            .request::<crate::rpc::Empty>("create_wallet", Params::Map(params)))?;
+        Ok(())
+    }
+
+    /// Opens an existing wallet file. See [`WalletClient::create_wallet`] for
+    /// why this doesn't go through `rpc_method!`.
+    pub async fn open_wallet(&self, filename: String, password: Option<String>) -> Fallible<()> {
+        let mut params = serde_json::Map::new();
+        crate::rpc::insert_param(&mut params, "filename", filename);
+        crate::rpc::insert_param(&mut params, "password", password);
+
+        await!(self
+            .inner
+            .request::<crate::rpc::Empty>("open_wallet", Params::Map(params)))?;
+        Ok(())
+    }
+
+    /// Closes the currently open wallet. See [`WalletClient::create_wallet`]
+    /// for why this doesn't go through `rpc_method!`.
+    pub async fn close_wallet(&self) -> Fallible<()> {
+        await!(self
+            .inner
+            .request::<crate::rpc::Empty>("close_wallet", Params::Map(serde_json::Map::new())))?;
+        Ok(())
+    }
+
+    /// Labels a subaddress. monerod expects the account/address indices
+    /// nested under a single `index: {major, minor}` object rather than as
+    /// flat arguments, so this doesn't go through `rpc_method!` either.
+    pub async fn label_address(
+        &self,
+        account_index: u64,
+        address_index: u64,
+        label: String,
+    ) -> Fallible<()> {
+        let mut args = serde_json::Map::new();
+        args.insert(
+            "index".to_string(),
+            json!({"major": account_index, "minor": address_index}),
+        );
+        args.insert("label".to_string(), label.into());
+
+        await!(self
+            .inner
+            .request::<crate::rpc::Empty>("label_address", Params::Map(args)))?;
+        Ok(())
+    }
+
+    /// Looks up a single transfer by transaction id, within the given
+    /// account (or the default account if `account_index` is `None`).
+    pub async fn get_transfer_by_txid(
+        &self,
+        txid: String,
+        account_index: Option<u64>,
+    ) -> Fallible<TransferEntry> {
+        #[derive(Deserialize)]
+        struct Response {
+            transfer: TransferEntry,
+        }
+
+        let mut params = serde_json::Map::new();
+        params.insert("txid".to_string(), txid.into());
+        if let Some(account_index) = account_index {
+            params.insert("account_index".to_string(), account_index.into());
+        }
+
+        Ok(await!(self
+            .inner
+            .request::<Response>("get_transfer_by_txid", Params::Map(params)))?
+        .transfer)
+    }
+
+    /// Restores a wallet file from its address and keys. Pass `spendkey:
+    /// None` to generate a view-only wallet.
+    pub async fn generate_from_keys(
+        &self,
+        filename: String,
+        address: Address,
+        spendkey: Option<HashString<PrivateKey>>,
+        viewkey: HashString<PrivateKey>,
+        restore_height: u64,
+        password: String,
+        autosave_current: bool,
+    ) -> Fallible<GenerateFromKeysResult> {
+        let mut params = serde_json::Map::new();
+        params.insert("filename".to_string(), filename.into());
+        params.insert("address".to_string(), serde_json::to_value(address).unwrap());
+        params.insert(
+            "spendkey".to_string(),
+            spendkey
+                .map(|k| hex::encode(k.0.bytes()))
+                .unwrap_or_default()
+                .into(),
+        );
+        params.insert("viewkey".to_string(), serde_json::to_value(viewkey).unwrap());
+        params.insert("restore_height".to_string(), restore_height.into());
+        params.insert("password".to_string(), password.into());
+        params.insert("autosave_current".to_string(), autosave_current.into());
+
+        await!(self
+            .inner
+            .request("generate_from_keys", Params::Map(params)))
+    }
+
+    pub async fn transfer(
+        &self,
+        destinations: HashMap<Address, u128>,
+        account_index: Option<u64>,
+        subaddr_indices: Option<Vec<u64>>,
+        priority: TransferPriority,
+        mixin: Option<u64>,
+        ring_size: Option<u64>,
+        unlock_time: Option<u64>,
+        payment_id: Option<PaymentId>,
+        do_not_relay: Option<bool>,
+    ) -> Fallible<TransferData> {
+        let mut args = serde_json::Map::default();
+        args["destinations"] = destinations
+            .into_iter()
+            .map(|(address, amount)| json!({"address": address, "amount": amount}))
+            .collect::<Vec<Value>>()
+            .into();
+        args["priority"] = serde_json::to_value(priority).unwrap();
+
+        if let Some(account_index) = account_index {
+            args["account_index"] = account_index.into();
+        }
+
+        if let Some(subaddr_indices) = subaddr_indices {
+            args["subaddr_indices"] = subaddr_indices
+                .into_iter()
+                .map(|v| v.into())
+                .collect::<Vec<Value>>()
+                .into();
+        }
+
+        if let Some(mixin) = mixin {
+            args["mixin"] = mixin.into();
+        }
+
+        if let Some(ring_size) = ring_size {
+            args["ring_size"] = ring_size.into();
+        }
+
+        if let Some(unlock_time) = unlock_time {
+            args["unlock_time"] = unlock_time.into();
+        }
+
+        if let Some(payment_id) = payment_id {
+            args["payment_id"] = serde_json::to_value(HashString(payment_id)).unwrap();
+        }
+
+        if let Some(do_not_relay) = do_not_relay {
+            args["do_not_relay"] = do_not_relay.into();
+        }
+
+        await!(self.inner.request("transfer", Params::Map(args)))
+    }
+
+    /// Sends the entire unlocked balance of the selected account/subaddresses
+    /// to a single destination, possibly splitting across several
+    /// transactions.
+    pub async fn sweep_all(
+        &self,
+        address: Address,
+        account_index: u64,
+        subaddr_indices: Option<Vec<u64>>,
+        priority: TransferPriority,
+        ring_size: Option<u64>,
+        unlock_time: Option<u64>,
+        do_not_relay: Option<bool>,
+    ) -> Fallible<SweepAllResult> {
+        let mut args = serde_json::Map::default();
+        args["address"] = serde_json::to_value(address).unwrap();
+        args["account_index"] = account_index.into();
+        args["priority"] = serde_json::to_value(priority).unwrap();
+        args["get_tx_keys"] = true.into();
+
+        if let Some(subaddr_indices) = subaddr_indices {
+            args["subaddr_indices"] = subaddr_indices
+                .into_iter()
+                .map(|v| v.into())
+                .collect::<Vec<Value>>()
+                .into();
+        }
+
+        if let Some(ring_size) = ring_size {
+            args["ring_size"] = ring_size.into();
+        }
+
+        if let Some(unlock_time) = unlock_time {
+            args["unlock_time"] = unlock_time.into();
+        }
+
+        if let Some(do_not_relay) = do_not_relay {
+            args["do_not_relay"] = do_not_relay.into();
+        }
+
+        await!(self.inner.request("sweep_all", Params::Map(args)))
+    }
+}